@@ -0,0 +1,56 @@
+// Copyright 2021 Graydon Hoare <graydon@pobox.com>
+// Licensed under ASL2 or MIT
+
+//! Iteration over the `source()` chain of an error, used by
+//! [`BacktraceError`](crate::BacktraceError)'s `Display` impl to print every
+//! cause rather than just the immediate `inner` error.
+
+use std::error::Error;
+
+/// An iterator over an error and each of its `source()`s, in order from
+/// the given error down to the deepest cause.
+pub struct Chain<'a> {
+    next: Option<&'a (dyn Error + 'static)>,
+}
+
+impl<'a> Chain<'a> {
+    pub(crate) fn new(head: &'a (dyn Error + 'static)) -> Self {
+        Chain { next: Some(head) }
+    }
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = &'a (dyn Error + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        self.next = current.source();
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContextError;
+    use std::io;
+
+    fn io_err(msg: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::Other, msg.to_string())
+    }
+
+    #[test]
+    fn chain_walks_full_source_depth() {
+        let middle = ContextError { context: "middle", inner: io_err("root cause") };
+        let outer = ContextError { context: "outer", inner: middle };
+
+        let messages: Vec<String> = Chain::new(&outer).map(|e| e.to_string()).collect();
+        assert_eq!(messages, vec!["outer", "middle", "root cause"]);
+    }
+
+    #[test]
+    fn chain_of_single_error_yields_one_item() {
+        let err = io_err("solo");
+        assert_eq!(Chain::new(&err).count(), 1);
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright 2021 Graydon Hoare <graydon@pobox.com>
+// Licensed under ASL2 or MIT
+
+//! Backtrace type used by this crate, switched by the `backtrace` cargo
+//! feature so that crates which don't want (or can't use) `std::backtrace`
+//! can still depend on `backtrace-error` as a thin `From`/`Display`/
+//! `ResultExt` wrapper.
+
+#[cfg(feature = "backtrace")]
+pub use std::backtrace::Backtrace;
+
+#[cfg(not(feature = "backtrace"))]
+mod shim {
+    use std::fmt::{self, Display};
+
+    /// Zero-sized stand-in for `std::backtrace::Backtrace` used when the
+    /// `backtrace` feature is disabled. Capture is a no-op and `Display`
+    /// always prints a fixed "unavailable" message.
+    #[derive(Debug)]
+    pub struct Backtrace;
+
+    impl Backtrace {
+        pub fn capture() -> Self {
+            Backtrace
+        }
+    }
+
+    impl Display for Backtrace {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "backtrace unavailable")
+        }
+    }
+}
+
+#[cfg(not(feature = "backtrace"))]
+pub use shim::Backtrace;
@@ -0,0 +1,112 @@
+// Copyright 2021 Graydon Hoare <graydon@pobox.com>
+// Licensed under ASL2 or MIT
+
+//! Support for attaching human-readable context to a [`BacktraceError`],
+//! in the style of `anyhow::Error::context`. The `.context`/`.with_context`
+//! methods live on [`ResultExt`](crate::ResultExt) rather than a separate
+//! trait, alongside `.unwrap_or_backtrace`/`.expect_or_backtrace`.
+
+use std::{error::Error, fmt::{Debug, Display}};
+
+use crate::BacktraceError;
+
+/// An error wrapping some other error `E` together with a context value `C`
+/// describing what was being attempted when `E` occurred.
+///
+/// `Display` prints only the context message, not the underlying error,
+/// matching `anyhow::Error`'s convention that the top-level message is the
+/// most recently attached context; `source()` returns the underlying error
+/// so the chain (e.g. [`BacktraceError::chain`](crate::BacktraceError::chain)
+/// or its `Display`) can print the rest without duplicating this frame's
+/// text.
+pub struct ContextError<E, C> {
+    pub context: C,
+    pub inner: E,
+}
+
+impl<E: Debug, C: Display> Debug for ContextError<E, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {:?}", self.context, self.inner)
+    }
+}
+
+impl<E, C: Display> Display for ContextError<E, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.context, f)
+    }
+}
+
+impl<E: Error + 'static, C: Display> Error for ContextError<E, C> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.inner)
+    }
+}
+
+impl<E> BacktraceError<E> {
+    /// Attach a context message to this error, keeping the original
+    /// backtrace but wrapping `inner` in a [`ContextError`] whose `Display`
+    /// prints only `ctx`; the underlying error is its `source()`.
+    pub fn context<C: Display + Send + Sync + 'static>(self, ctx: C) -> BacktraceError<ContextError<E, C>> {
+        BacktraceError {
+            inner: ContextError {
+                context: ctx,
+                inner: self.inner,
+            },
+            backtrace: self.backtrace,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BacktraceError, ResultExt};
+    use std::io;
+
+    fn io_err(msg: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::Other, msg.to_string())
+    }
+
+    #[test]
+    fn source_returns_inner() {
+        let ctx = ContextError { context: "reading config", inner: io_err("boom") };
+        let source = ctx.source().expect("source");
+        assert_eq!(source.to_string(), "boom");
+    }
+
+    #[test]
+    fn display_prints_only_context() {
+        let ctx = ContextError { context: "reading config", inner: io_err("boom") };
+        assert_eq!(ctx.to_string(), "reading config");
+    }
+
+    #[test]
+    fn backtrace_error_context_preserves_backtrace() {
+        let err = BacktraceError::without_backtrace(io_err("boom"));
+        let wrapped = err.context("reading config");
+        assert!(wrapped.backtrace().is_none());
+        assert_eq!(wrapped.inner.context, "reading config");
+    }
+
+    #[test]
+    fn result_ext_context_wraps_err() {
+        let result: Result<(), BacktraceError<io::Error>> =
+            Err(BacktraceError::without_backtrace(io_err("boom")));
+        let wrapped = result.context("reading config");
+        let err = wrapped.unwrap_err();
+        assert_eq!(err.inner.context, "reading config");
+        assert_eq!(err.inner.inner.to_string(), "boom");
+    }
+
+    #[test]
+    fn result_ext_with_context_is_lazy() {
+        let ok: Result<i32, BacktraceError<io::Error>> = Ok(5);
+        let mut called = false;
+        let result = ok.with_context(|| {
+            called = true;
+            "should not run"
+        });
+        assert_eq!(result.unwrap(), 5);
+        assert!(!called);
+    }
+}
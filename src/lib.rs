@@ -50,23 +50,136 @@
 //! backtraces for errors when they occur, and print them out sometime later.
 //!
 //! I figured maybe someone out there has the same need, so am publishing it.
+//!
+//! The `backtrace` cargo feature (on by default) controls whether this
+//! crate actually uses `std::backtrace::Backtrace`. Turning it off swaps in
+//! a zero-sized placeholder so the crate still compiles on toolchains or in
+//! environments where real backtraces aren't available, with capture
+//! becoming a no-op.
 
-#![feature(backtrace, negative_impls, auto_traits)]
+#![cfg_attr(feature = "backtrace", feature(backtrace))]
+#![feature(negative_impls, auto_traits)]
 #![feature(try_trait_v2)]
+#![feature(specialization)]
+#![allow(incomplete_features)]
+
+use std::{error::Error, fmt::Display};
+#[cfg(feature = "backtrace")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod compat;
+pub use compat::Backtrace;
+
+mod macros;
+
+mod chain;
+pub use chain::Chain;
+
+mod context;
+pub use context::ContextError;
+
+mod reporter;
+pub use reporter::set_error_reporter;
 
-use std::{error::Error, backtrace::Backtrace, fmt::Display};
+mod redact;
+pub use redact::{Redact, Redacted};
+
+// 0 = not yet resolved, 1 = resolved disabled, 2 = resolved enabled.
+#[cfg(feature = "backtrace")]
+static BACKTRACE_ENABLED: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether backtrace capture is currently enabled, per `RUST_LIB_BACKTRACE`
+/// (preferred) or `RUST_BACKTRACE`. Resolved once and cached so the common
+/// disabled case is a single relaxed load rather than repeated env lookups.
+///
+/// Always `false` when the `backtrace` feature is off, since `Backtrace` is
+/// then just a zero-sized placeholder anyway.
+#[cfg(feature = "backtrace")]
+fn backtrace_enabled() -> bool {
+    match BACKTRACE_ENABLED.load(Ordering::Relaxed) {
+        0 => {
+            let enabled = std::env::var_os("RUST_LIB_BACKTRACE")
+                .or_else(|| std::env::var_os("RUST_BACKTRACE"))
+                .map_or(false, |v| v != "0");
+            BACKTRACE_ENABLED.store(if enabled { 2 } else { 1 }, Ordering::Relaxed);
+            enabled
+        }
+        1 => false,
+        _ => true,
+    }
+}
+
+#[cfg(not(feature = "backtrace"))]
+fn backtrace_enabled() -> bool {
+    false
+}
+
+/// Capture a backtrace, unless backtraces are disabled, in which case this
+/// is a no-op that returns `None` rather than an empty capture.
+fn capture_backtrace() -> Option<Backtrace> {
+    if backtrace_enabled() {
+        Some(Backtrace::capture())
+    } else {
+        None
+    }
+}
 
 #[derive(Debug)]
 pub struct BacktraceError<E> {
     pub inner: E,
-    pub backtrace: Backtrace
+    pub backtrace: Option<Backtrace>
+}
+
+impl<E> BacktraceError<E> {
+    /// Construct a `BacktraceError` that never captures a backtrace,
+    /// regardless of `RUST_BACKTRACE`. Useful for error variants that are
+    /// used purely as control flow, where capture cost is unwanted.
+    pub fn without_backtrace(inner: E) -> Self {
+        Self { inner, backtrace: None }
+    }
+
+    /// The backtrace captured at the point this error was created, or
+    /// `None` if backtraces were disabled (or capture was skipped via
+    /// [`BacktraceError::without_backtrace`]).
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+}
+
+impl<E: Redact> BacktraceError<E> {
+    /// Render `inner` through its [`Redact`] impl instead of its normal
+    /// `Display`, scrubbing sensitive fields before the string is emitted
+    /// to the error reporter or printed to an end user.
+    pub fn redacted_display(&self) -> Redacted<'_, E> {
+        Redacted(&self.inner)
+    }
 }
 
-impl<E:Error> Display for BacktraceError<E> {
+impl<E: Error + 'static> BacktraceError<E> {
+    /// Iterate `inner` and the full chain of causes below it, following
+    /// `Error::source()` links. The first item is `inner` itself.
+    pub fn chain(&self) -> Chain<'_> {
+        Chain::new(&self.inner)
+    }
+}
+
+impl<E: Error + 'static> Display for BacktraceError<E> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Initial error: {:}", self.inner)?;
-        writeln!(f, "Error context:")?;
-        writeln!(f, "{:}", self.backtrace)
+        if let Some(backtrace) = &self.backtrace {
+            writeln!(f, "Error context:")?;
+            writeln!(f, "{:}", backtrace)?;
+        }
+        for cause in self.chain().skip(1) {
+            // Don't also print `cause.backtrace()` here: if `cause` is
+            // itself a `BacktraceError`, its own `Display` impl (invoked by
+            // the `{}` below) already rendered an "Error context:" section
+            // for the same capture, and printing it again would duplicate
+            // it. No other error type in practice implements
+            // `Error::backtrace()`.
+            writeln!(f, "Caused by: {:}", cause)?;
+        }
+        Ok(())
     }
 }
 
@@ -75,8 +188,9 @@ impl<E:Error + 'static> Error for BacktraceError<E> {
         Some(&self.inner)
     }
 
+    #[cfg(feature = "backtrace")]
     fn backtrace(&self) -> Option<&Backtrace> {
-        Some(&self.backtrace)
+        self.backtrace.as_ref()
     }
 }
 
@@ -134,6 +248,11 @@ impl<T: IntoBacktraceError<U>, U> From<T> for BacktraceError<U> where (T, Backtr
 
 
 
+// Note: unlike the `From<U>` impl below, this rewrap does not call the
+// error reporter. It's converting the inner type of an *existing*
+// `BacktraceError`, preserving its original backtrace — if that
+// `BacktraceError` came from `From<U>`, its capture site was already
+// reported then (one built by `without_backtrace` is never reported).
 impl<T: From<U>, U> From<BacktraceError<U>> for BacktraceError<T> where (T, U): NotEqual {
     fn from(backtrace_error: BacktraceError<U>) -> Self {
         Self {
@@ -152,25 +271,43 @@ impl<T> !NotEqual for (T, T) {}
 impl NotEqual for dyn Error + Sync + std::marker::Send + 'static {}
 
 
-impl<T: From<U>, U> From<U> for BacktraceError<T> where (U, BacktraceError<T>): NotEqual{
+// Note: `T` is bounded by `Error + 'static` here, not just `From<U>`. That's
+// narrower than a bare `T: From<U>` would be, so `BacktraceError<T>` can no
+// longer wrap a `T` that isn't itself a `std::error::Error` via `?`. This
+// isn't incidental: most of what `BacktraceError<T>` offers (`Display`,
+// `Error`, `chain()`, `ResultExt`) already requires `T: Error + 'static`,
+// so a `T` without it couldn't do much useful here anyway. (The error
+// reporter itself only needs `T: Display`, for the `Redacted` text.)
+impl<T: From<U> + Error + 'static, U> From<U> for BacktraceError<T> where (U, BacktraceError<T>): NotEqual{
     fn from(residual: U) -> Self {
-        Self {
-            inner: T::from(residual),
-            backtrace: Backtrace::capture()
-        }
+        let inner = T::from(residual);
+        let backtrace = capture_backtrace();
+        reporter::report(&Redacted(&inner), backtrace.as_ref());
+        Self { inner, backtrace }
     }
 }
 
 pub trait ResultExt: Sized {
     type T;
+    type E;
+
     fn unwrap_or_backtrace(self) -> Self::T {
         self.expect_or_backtrace("ResultExt::unwrap_or_backtrace found Err")
     }
     fn expect_or_backtrace(self, msg: &str) -> Self::T;
+
+    /// Attach a context message to the `Err` arm of this result.
+    fn context<C: Display + Send + Sync + 'static>(self, ctx: C) -> Result<Self::T, BacktraceError<ContextError<Self::E, C>>>;
+
+    /// Attach a lazily-computed context message to the `Err` arm of this
+    /// result. The closure is only called when the result is an `Err`.
+    fn with_context<C: Display + Send + Sync + 'static, F: FnOnce() -> C>(self, f: F) -> Result<Self::T, BacktraceError<ContextError<Self::E, C>>>;
 }
 
-impl<T, E:Error> ResultExt for Result<T,BacktraceError<E>> {
+impl<T, E: Error + 'static> ResultExt for Result<T,BacktraceError<E>> {
     type T = T;
+    type E = E;
+
     fn expect_or_backtrace(self, msg: &str) -> T {
         match self {
             Ok(ok) => ok,
@@ -182,4 +319,36 @@ impl<T, E:Error> ResultExt for Result<T,BacktraceError<E>> {
             },
         }
     }
+
+    fn context<C: Display + Send + Sync + 'static>(self, ctx: C) -> Result<T, BacktraceError<ContextError<E, C>>> {
+        self.map_err(|e| e.context(ctx))
+    }
+
+    fn with_context<C: Display + Send + Sync + 'static, F: FnOnce() -> C>(self, f: F) -> Result<T, BacktraceError<ContextError<E, C>>> {
+        self.map_err(|e| e.context(f()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn io_err(msg: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::Other, msg.to_string())
+    }
+
+    #[test]
+    fn without_backtrace_never_captures() {
+        let err = BacktraceError::without_backtrace(io_err("boom"));
+        assert!(err.backtrace().is_none());
+    }
+
+    #[test]
+    fn without_backtrace_display_omits_context_section() {
+        let err = BacktraceError::without_backtrace(io_err("boom"));
+        let rendered = err.to_string();
+        assert!(rendered.contains("Initial error: boom"));
+        assert!(!rendered.contains("Error context:"));
+    }
 }
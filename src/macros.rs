@@ -0,0 +1,70 @@
+// Copyright 2021 Graydon Hoare <graydon@pobox.com>
+// Licensed under ASL2 or MIT
+
+//! Declarative macros for constructing and propagating [`BacktraceError`]
+//! without relying solely on `?`/`From`, for the cases where an error
+//! originates from a literal in the middle of a function rather than a
+//! `From`-convertible call.
+
+/// Return early with `Err(BacktraceError::from(err))`, capturing a fresh
+/// backtrace at this macro's call site.
+///
+/// ```
+/// # use backtrace_error::{BacktraceError, throw};
+/// fn check(n: i32) -> Result<i32, BacktraceError<std::io::Error>> {
+///     if n < 0 {
+///         throw!(std::io::Error::new(std::io::ErrorKind::InvalidInput, "negative"));
+///     }
+///     Ok(n)
+/// }
+/// ```
+#[macro_export]
+macro_rules! throw {
+    ($err:expr) => {
+        return ::core::result::Result::Err($crate::BacktraceError::from($err))
+    };
+}
+
+/// Propagate an existing `Result<_, BacktraceError<_>>`, returning early on
+/// `Err` while preserving the original capture site's backtrace, or
+/// yielding the `Ok` value otherwise.
+///
+/// ```
+/// # use backtrace_error::{BacktraceError, rethrow};
+/// fn inner() -> Result<i32, BacktraceError<std::io::Error>> {
+///     Ok(1)
+/// }
+/// fn outer() -> Result<i32, BacktraceError<std::io::Error>> {
+///     let n = rethrow!(inner());
+///     Ok(n + 1)
+/// }
+/// ```
+#[macro_export]
+macro_rules! rethrow {
+    ($result:expr) => {
+        match $result {
+            ::core::result::Result::Ok(val) => val,
+            ::core::result::Result::Err(err) => {
+                return ::core::result::Result::Err($crate::BacktraceError::from(err))
+            }
+        }
+    };
+}
+
+/// Return early with `throw!(err)` unless `cond` holds.
+///
+/// ```
+/// # use backtrace_error::{BacktraceError, ensure};
+/// fn check(n: i32) -> Result<i32, BacktraceError<std::io::Error>> {
+///     ensure!(n >= 0, std::io::Error::new(std::io::ErrorKind::InvalidInput, "negative"));
+///     Ok(n)
+/// }
+/// ```
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr, $err:expr) => {
+        if !($cond) {
+            $crate::throw!($err);
+        }
+    };
+}
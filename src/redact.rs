@@ -0,0 +1,74 @@
+// Copyright 2021 Graydon Hoare <graydon@pobox.com>
+// Licensed under ASL2 or MIT
+
+//! Support for scrubbing sensitive fields (paths, tokens) out of an error's
+//! rendered text before it reaches the error reporter or an end user.
+
+use std::fmt::{self, Display};
+
+/// Implemented by error types that know how to render themselves with
+/// sensitive fields redacted, as an alternative to their normal `Display`.
+///
+/// Every `Display` type gets a default impl that just falls back to its
+/// normal `Display` output, so [`Redacted`] (and anything built on it, such
+/// as the error reporter) works even for error types that don't carry
+/// anything worth scrubbing. Override `redact` for types that do.
+pub trait Redact {
+    /// Write a redacted form of this value, in place of its normal
+    /// `Display` output.
+    fn redact(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+impl<T: Display> Redact for T {
+    default fn redact(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// A `Display` adapter that renders a [`Redact`] value through its
+/// redaction rather than its normal `Display` impl.
+pub struct Redacted<'a, T: ?Sized>(pub(crate) &'a T);
+
+impl<'a, T: Redact + ?Sized> Display for Redacted<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.redact(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt;
+
+    struct Plain(&'static str);
+
+    impl fmt::Display for Plain {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    struct Secret(&'static str);
+
+    impl fmt::Display for Secret {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl Redact for Secret {
+        fn redact(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "[REDACTED]")
+        }
+    }
+
+    #[test]
+    fn default_redact_falls_back_to_display() {
+        assert_eq!(Redacted(&Plain("hello")).to_string(), "hello");
+    }
+
+    #[test]
+    fn custom_redact_overrides_display() {
+        assert_eq!(Redacted(&Secret("token=abc123")).to_string(), "[REDACTED]");
+    }
+}
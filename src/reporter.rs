@@ -0,0 +1,112 @@
+// Copyright 2021 Graydon Hoare <graydon@pobox.com>
+// Licensed under ASL2 or MIT
+
+//! An optional global hook invoked the moment a fresh
+//! [`BacktraceError`](crate::BacktraceError) wraps a new error, so
+//! telemetry/logging can record the error and its capture site centrally
+//! rather than only at the eventual `unwrap_or_backtrace`.
+
+use std::{fmt::Display, sync::RwLock};
+
+use crate::Backtrace;
+
+type ReporterFn = dyn Fn(&dyn Display, Option<&Backtrace>) + Send + Sync;
+
+static REPORTER: RwLock<Option<Box<ReporterFn>>> = RwLock::new(None);
+
+/// Install a global error reporter. It is called every time a fresh
+/// `BacktraceError` is created from an error via `From`, before the error
+/// is ever unwrapped or printed.
+///
+/// The text handed to the reporter is always the error's [`Redact`]-ed
+/// rendering rather than its raw `Display`, so a reporter can never
+/// accidentally see unredacted output just because it chose to `Display`
+/// what it was given. `backtrace` is `None` when backtrace capture is
+/// disabled; the reporter still runs in that case, since "wrapped" doesn't
+/// require a backtrace to have been captured. Errors built with
+/// [`BacktraceError::without_backtrace`] don't go through `From` and are
+/// never reported.
+///
+/// Not called when an existing `BacktraceError` is merely rewrapped into a
+/// different inner error type (the `From<BacktraceError<U>>` conversion):
+/// that's the same capture site as the original wrap, not a new one.
+///
+/// Replaces any previously installed reporter.
+///
+/// [`Redact`]: crate::Redact
+/// [`BacktraceError::without_backtrace`]: crate::BacktraceError::without_backtrace
+pub fn set_error_reporter<F>(reporter: F)
+where
+    F: Fn(&dyn Display, Option<&Backtrace>) + Send + Sync + 'static,
+{
+    *REPORTER.write().unwrap() = Some(Box::new(reporter));
+}
+
+pub(crate) fn report(text: &dyn Display, backtrace: Option<&Backtrace>) {
+    if let Ok(guard) = REPORTER.read() {
+        if let Some(reporter) = guard.as_ref() {
+            reporter(text, backtrace);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BacktraceError;
+    use std::{
+        fmt,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    #[derive(Debug)]
+    struct RawError(String);
+
+    impl fmt::Display for RawError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl std::error::Error for RawError {}
+
+    #[derive(Debug)]
+    struct TestError(RawError);
+
+    impl fmt::Display for TestError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl std::error::Error for TestError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    impl From<RawError> for TestError {
+        fn from(e: RawError) -> Self {
+            TestError(e)
+        }
+    }
+
+    #[test]
+    fn reports_on_fresh_wrap_not_on_rewrap() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        set_error_reporter(|_text, _backtrace| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let _fresh: BacktraceError<TestError> = RawError("boom".into()).into();
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+        let existing = BacktraceError::without_backtrace(RawError("boom".into()));
+        let _rewrapped: BacktraceError<TestError> = existing.into();
+        assert_eq!(
+            CALLS.load(Ordering::SeqCst),
+            1,
+            "rewrap must not re-report"
+        );
+    }
+}